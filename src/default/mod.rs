@@ -1,18 +1,18 @@
-/// A placeholder for the `struct Limit` type of unsupported operating systems.
-pub struct Limits();
+//! Fallback implementation for platforms other than GNU/Linux.
+//!
+//! On unix-like platforms (macOS, the BSDs, Android, ...) this is backed by `getrlimit(2)` for the
+//! calling process and `prlimit(2)` (where the platform actually implements that syscall) for
+//! arbitrary processes. `libc` does not define those POSIX rlimit symbols on non-unix targets (e.g.
+//! Windows, wasm32), so on those targets `get_pid_limits` simply returns `Error::UnsupportedOS`.
 
-/// Always return an `UnsupportedOS` error for unsupported operating systems.
-pub fn get_pid_limits(pid: u32) -> Result<Limits, crate::Error> {
-    Err(crate::Error::UnsupportedOS)
-}
+#[cfg(unix)]
+mod unix;
 
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn test_unimplemented() {
-        let result = crate::get_pid_limits(0).unwrap_err();
-        let expected = crate::Error::UnsupportedOS;
+#[cfg(unix)]
+pub use self::unix::get_pid_limits;
 
-        assert_eq!(result, expected);
-    }
+/// No rlimit syscall is available on this platform at all.
+#[cfg(not(unix))]
+pub fn get_pid_limits(_pid: u32) -> Result<crate::Limits, crate::Error> {
+    Err(crate::Error::UnsupportedOS)
 }
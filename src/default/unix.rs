@@ -0,0 +1,133 @@
+//! `getrlimit(2)`/`prlimit(2)`-backed implementation for unix-like platforms other than GNU/Linux.
+
+use crate::{Limit, Limits, Unit};
+
+/// Read a single limit for the calling process via `getrlimit(2)`.
+fn own_limit(resource: libc::c_int, unit: Unit) -> Result<Limit, crate::Error> {
+    let mut rlimit = std::mem::MaybeUninit::<libc::rlimit>::uninit();
+
+    let result = unsafe { libc::getrlimit(resource, rlimit.as_mut_ptr()) };
+    if result != 0 {
+        return Err(crate::Error::GetLimitFailed(
+            std::process::id(),
+            std::io::Error::last_os_error(),
+        ));
+    }
+
+    // Safe because `getrlimit` only returned successfully after writing into `rlimit`.
+    let rlimit = unsafe { rlimit.assume_init() };
+    let to_option = |value: libc::rlim_t| {
+        if value == libc::RLIM_INFINITY {
+            None
+        } else {
+            Some(value as u64)
+        }
+    };
+
+    Ok(Limit {
+        soft: to_option(rlimit.rlim_cur),
+        hard: to_option(rlimit.rlim_max),
+        unit,
+    })
+}
+
+/// Populate the limits of the calling process via `getrlimit(2)`.
+///
+/// Only the ten resources that the BSDs, macOS and Android share with GNU/Linux are populated; the
+/// remaining, Linux-specific fields of `Limits` are left at their default, i.e. `unlimited`.
+fn own_limits_via_getrlimit() -> Result<Limits, crate::Error> {
+    Ok(Limits {
+        max_cpu_time: own_limit(libc::RLIMIT_CPU, Unit::Seconds)?,
+        max_file_size: own_limit(libc::RLIMIT_FSIZE, Unit::Bytes)?,
+        max_data_size: own_limit(libc::RLIMIT_DATA, Unit::Bytes)?,
+        max_stack_size: own_limit(libc::RLIMIT_STACK, Unit::Bytes)?,
+        max_core_file_size: own_limit(libc::RLIMIT_CORE, Unit::Bytes)?,
+        max_resident_set: own_limit(libc::RLIMIT_RSS, Unit::Bytes)?,
+        max_processes: own_limit(libc::RLIMIT_NPROC, Unit::Processes)?,
+        max_open_files: own_limit(libc::RLIMIT_NOFILE, Unit::Files)?,
+        max_locked_memory: own_limit(libc::RLIMIT_MEMLOCK, Unit::Bytes)?,
+        max_address_space: own_limit(libc::RLIMIT_AS, Unit::Bytes)?,
+        ..Limits::default()
+    })
+}
+
+/// Read the limits of an arbitrary process via `prlimit(2)`, on the platforms that implement it.
+#[cfg(target_os = "android")]
+fn pid_limits_via_prlimit(pid: u32) -> Result<Limits, crate::Error> {
+    fn pid_limit(pid: u32, resource: libc::c_int, unit: Unit) -> Result<Limit, crate::Error> {
+        let mut rlimit = std::mem::MaybeUninit::<libc::rlimit64>::uninit();
+
+        let result = unsafe {
+            libc::prlimit64(
+                pid as libc::pid_t,
+                resource,
+                std::ptr::null(),
+                rlimit.as_mut_ptr(),
+            )
+        };
+        if result != 0 {
+            return Err(crate::Error::GetLimitFailed(
+                pid,
+                std::io::Error::last_os_error(),
+            ));
+        }
+
+        // Safe because `prlimit64` only returned successfully after writing into `rlimit`.
+        let rlimit = unsafe { rlimit.assume_init() };
+        let to_option = |value: u64| {
+            if value == libc::RLIM_INFINITY {
+                None
+            } else {
+                Some(value)
+            }
+        };
+
+        Ok(Limit {
+            soft: to_option(rlimit.rlim_cur),
+            hard: to_option(rlimit.rlim_max),
+            unit,
+        })
+    }
+
+    Ok(Limits {
+        max_cpu_time: pid_limit(pid, libc::RLIMIT_CPU, Unit::Seconds)?,
+        max_file_size: pid_limit(pid, libc::RLIMIT_FSIZE, Unit::Bytes)?,
+        max_data_size: pid_limit(pid, libc::RLIMIT_DATA, Unit::Bytes)?,
+        max_stack_size: pid_limit(pid, libc::RLIMIT_STACK, Unit::Bytes)?,
+        max_core_file_size: pid_limit(pid, libc::RLIMIT_CORE, Unit::Bytes)?,
+        max_resident_set: pid_limit(pid, libc::RLIMIT_RSS, Unit::Bytes)?,
+        max_processes: pid_limit(pid, libc::RLIMIT_NPROC, Unit::Processes)?,
+        max_open_files: pid_limit(pid, libc::RLIMIT_NOFILE, Unit::Files)?,
+        max_locked_memory: pid_limit(pid, libc::RLIMIT_MEMLOCK, Unit::Bytes)?,
+        max_address_space: pid_limit(pid, libc::RLIMIT_AS, Unit::Bytes)?,
+        ..Limits::default()
+    })
+}
+
+/// No syscall is available on this platform to read the limits of an arbitrary other process.
+#[cfg(not(target_os = "android"))]
+fn pid_limits_via_prlimit(_pid: u32) -> Result<Limits, crate::Error> {
+    Err(crate::Error::UnsupportedOS)
+}
+
+/// Get the limits for a specific process identifier.
+///
+/// For the calling process (`pid == 0`, or `pid` equal to our own process id), this is backed by
+/// `getrlimit(2)`, which every target this module compiles for implements. For any other process,
+/// `prlimit(2)` is used where the platform supports it; elsewhere, `Error::UnsupportedOS` is
+/// returned.
+pub fn get_pid_limits(pid: u32) -> Result<Limits, crate::Error> {
+    if pid == 0 || pid == std::process::id() {
+        return own_limits_via_getrlimit();
+    }
+
+    pid_limits_via_prlimit(pid)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_own_limits_does_not_panic() {
+        crate::get_own_limits().unwrap();
+    }
+}
@@ -1,11 +1,17 @@
 //! Utilities for determining the limits that an operating system enforces on a given particular
 //! process.
 //!
-//! In its current implementation, this crate allows convenient read of the `/proc/<pid>/limits`
-//! file on GNU/Linux. On any other platform, the provided methods will return an error so that the
-//! user can decide what to do in the absence of information about limits.
+//! This crate reads (and, on GNU/Linux, writes) process limits: on GNU/Linux by parsing the
+//! `/proc/<pid>/limits` file and calling `prlimit(2)`, and on other unix-like platforms (macOS,
+//! the BSDs, Android) by calling `getrlimit(2)`/`prlimit(2)` directly. Every platform populates
+//! the very same [`Limits`] structure, so callers do not need to branch on `cfg` to read a limit;
+//! platforms with no limit syscall at all return `Error::UnsupportedOS`.
 //!
 //! Support for other operating systems and platforms may be added on demand.
+//!
+//! Enable the `serde` feature to derive `Serialize`/`Deserialize` on [`Limits`], [`Limit`], and the
+//! [`Unit`] and [`ResourceKind`] enums, for consumers that want to ship limits off to a monitoring or
+//! telemetry system.
 
 use thiserror::Error;
 
@@ -15,7 +21,7 @@ mod linux;
 #[cfg(target_os = "linux")]
 pub use crate::linux::*;
 
-// Placeholder for all other platforms
+// Fallback support for all other platforms, through `getrlimit(2)`/`prlimit(2)`.
 #[cfg(not(target_os = "linux"))]
 mod default;
 #[cfg(not(target_os = "linux"))]
@@ -29,6 +35,10 @@ pub enum Error {
     UnsupportedOS,
     #[error("Proc file not found at `{}`: {}", .0, .1)]
     ProcFileNotFound(String, #[source] std::io::Error),
+    #[error("Failed to get a limit for process `{}`: {}", .0, .1)]
+    GetLimitFailed(u32, #[source] std::io::Error),
+    #[error("Failed to set a limit for process `{}`: {}", .0, .1)]
+    SetLimitFailed(u32, #[source] std::io::Error),
 }
 
 /// Get the limits for the process in which we are running (our own process id).
@@ -37,3 +47,314 @@ pub fn get_own_limits() -> Result<Limits, crate::Error> {
 
     get_pid_limits(own_pid)
 }
+
+/// A limit for a limitable property of a process.
+///
+/// Any given limit always contain a _soft_ and a _hard_ limit.
+///
+/// A soft or hard limited whose value is `None` here means there is no actual limit, i.e. the value
+/// found in `/proc/<pid>/limits` (or returned by `getrlimit(2)`) is `unlimited`. With the `serde`
+/// feature enabled, this is serialized as `null`.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Limit {
+    pub soft: Option<u64>,
+    pub hard: Option<u64>,
+    pub unit: Unit,
+}
+
+impl Limit {
+    /// Interpret the soft limit as a byte count, if `unit` denominates one.
+    pub fn soft_as_bytes(&self) -> Option<u64> {
+        self.soft.and_then(|value| self.unit.as_bytes(value))
+    }
+
+    /// Interpret the hard limit as a byte count, if `unit` denominates one.
+    pub fn hard_as_bytes(&self) -> Option<u64> {
+        self.hard.and_then(|value| self.unit.as_bytes(value))
+    }
+
+    /// Interpret the soft limit as a `Duration`, if `unit` denominates one.
+    pub fn soft_as_duration(&self) -> Option<std::time::Duration> {
+        self.soft.and_then(|value| self.unit.as_duration(value))
+    }
+
+    /// Interpret the hard limit as a `Duration`, if `unit` denominates one.
+    pub fn hard_as_duration(&self) -> Option<std::time::Duration> {
+        self.hard.and_then(|value| self.unit.as_duration(value))
+    }
+}
+
+/// The unit that the raw value of a [`Limit`] is denominated in, as found in the fourth column of
+/// `/proc/<pid>/limits`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Unit {
+    Seconds,
+    Bytes,
+    Microseconds,
+    Processes,
+    Files,
+    Signals,
+    Locks,
+    Unitless,
+}
+
+impl Unit {
+    /// Interpret a raw limit value as a byte count, if this unit denominates one.
+    pub fn as_bytes(self, value: u64) -> Option<u64> {
+        match self {
+            Unit::Bytes => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Interpret a raw limit value as a `Duration`, if this unit denominates one.
+    pub fn as_duration(self, value: u64) -> Option<std::time::Duration> {
+        match self {
+            Unit::Seconds => Some(std::time::Duration::from_secs(value)),
+            Unit::Microseconds => Some(std::time::Duration::from_micros(value)),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Unit {
+    fn default() -> Self {
+        Unit::Unitless
+    }
+}
+
+/// A structure containing all possible properties that can be limited by an operating system.
+#[derive(Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Limits {
+    pub max_cpu_time: Limit,
+    pub max_file_size: Limit,
+    pub max_data_size: Limit,
+    pub max_stack_size: Limit,
+    pub max_core_file_size: Limit,
+    pub max_resident_set: Limit,
+    pub max_processes: Limit,
+    pub max_open_files: Limit,
+    pub max_locked_memory: Limit,
+    pub max_address_space: Limit,
+    pub max_file_locks: Limit,
+    pub max_pending_signals: Limit,
+    pub max_msgqueue_size: Limit,
+    pub max_nice_priority: Limit,
+    pub max_realtime_priority: Limit,
+    pub max_realtime_timeout: Limit,
+}
+
+/// Identifies a single limitable property of a process, for use with `set_pid_limit` and
+/// `set_own_limit`.
+///
+/// Each variant corresponds to one field of [`Limits`] and to the `RLIMIT_*` constant that the
+/// kernel uses to refer to it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResourceKind {
+    MaxCpuTime,
+    MaxFileSize,
+    MaxDataSize,
+    MaxStackSize,
+    MaxCoreFileSize,
+    MaxResidentSet,
+    MaxProcesses,
+    MaxOpenFiles,
+    MaxLockedMemory,
+    MaxAddressSpace,
+    MaxFileLocks,
+    MaxPendingSignals,
+    MaxMsgqueueSize,
+    MaxNicePriority,
+    MaxRealtimePriority,
+    MaxRealtimeTimeout,
+}
+
+impl ResourceKind {
+    /// All resource kinds, in the same order that [`Limits::iter`] yields them.
+    pub const ALL: [ResourceKind; 16] = [
+        ResourceKind::MaxCpuTime,
+        ResourceKind::MaxFileSize,
+        ResourceKind::MaxDataSize,
+        ResourceKind::MaxStackSize,
+        ResourceKind::MaxCoreFileSize,
+        ResourceKind::MaxResidentSet,
+        ResourceKind::MaxProcesses,
+        ResourceKind::MaxOpenFiles,
+        ResourceKind::MaxLockedMemory,
+        ResourceKind::MaxAddressSpace,
+        ResourceKind::MaxFileLocks,
+        ResourceKind::MaxPendingSignals,
+        ResourceKind::MaxMsgqueueSize,
+        ResourceKind::MaxNicePriority,
+        ResourceKind::MaxRealtimePriority,
+        ResourceKind::MaxRealtimeTimeout,
+    ];
+
+    /// A human-readable label for this resource, as found in `/proc/<pid>/limits`.
+    pub fn label(self) -> &'static str {
+        match self {
+            ResourceKind::MaxCpuTime => "Max cpu time",
+            ResourceKind::MaxFileSize => "Max file size",
+            ResourceKind::MaxDataSize => "Max data size",
+            ResourceKind::MaxStackSize => "Max stack size",
+            ResourceKind::MaxCoreFileSize => "Max core file size",
+            ResourceKind::MaxResidentSet => "Max resident set",
+            ResourceKind::MaxProcesses => "Max processes",
+            ResourceKind::MaxOpenFiles => "Max open files",
+            ResourceKind::MaxLockedMemory => "Max locked memory",
+            ResourceKind::MaxAddressSpace => "Max address space",
+            ResourceKind::MaxFileLocks => "Max file locks",
+            ResourceKind::MaxPendingSignals => "Max pending signals",
+            ResourceKind::MaxMsgqueueSize => "Max msgqueue size",
+            ResourceKind::MaxNicePriority => "Max nice priority",
+            ResourceKind::MaxRealtimePriority => "Max realtime priority",
+            ResourceKind::MaxRealtimeTimeout => "Max realtime timeout",
+        }
+    }
+
+    /// The `ulimit` short flag for this resource, e.g. `-t` for CPU time.
+    pub fn flag(self) -> &'static str {
+        match self {
+            ResourceKind::MaxCpuTime => "-t",
+            ResourceKind::MaxFileSize => "-f",
+            ResourceKind::MaxDataSize => "-d",
+            ResourceKind::MaxStackSize => "-s",
+            ResourceKind::MaxCoreFileSize => "-c",
+            ResourceKind::MaxResidentSet => "-m",
+            ResourceKind::MaxProcesses => "-u",
+            ResourceKind::MaxOpenFiles => "-n",
+            ResourceKind::MaxLockedMemory => "-l",
+            ResourceKind::MaxAddressSpace => "-v",
+            ResourceKind::MaxFileLocks => "-x",
+            ResourceKind::MaxPendingSignals => "-i",
+            ResourceKind::MaxMsgqueueSize => "-q",
+            ResourceKind::MaxNicePriority => "-e",
+            ResourceKind::MaxRealtimePriority => "-r",
+            ResourceKind::MaxRealtimeTimeout => "-R",
+        }
+    }
+
+    /// The `Unit` that this resource's raw limit values are denominated in.
+    pub fn unit(self) -> Unit {
+        match self {
+            ResourceKind::MaxCpuTime => Unit::Seconds,
+            ResourceKind::MaxFileSize => Unit::Bytes,
+            ResourceKind::MaxDataSize => Unit::Bytes,
+            ResourceKind::MaxStackSize => Unit::Bytes,
+            ResourceKind::MaxCoreFileSize => Unit::Bytes,
+            ResourceKind::MaxResidentSet => Unit::Bytes,
+            ResourceKind::MaxProcesses => Unit::Processes,
+            ResourceKind::MaxOpenFiles => Unit::Files,
+            ResourceKind::MaxLockedMemory => Unit::Bytes,
+            ResourceKind::MaxAddressSpace => Unit::Bytes,
+            ResourceKind::MaxFileLocks => Unit::Locks,
+            ResourceKind::MaxPendingSignals => Unit::Signals,
+            ResourceKind::MaxMsgqueueSize => Unit::Bytes,
+            ResourceKind::MaxNicePriority => Unit::Unitless,
+            ResourceKind::MaxRealtimePriority => Unit::Unitless,
+            ResourceKind::MaxRealtimeTimeout => Unit::Microseconds,
+        }
+    }
+}
+
+impl Limits {
+    /// Get the limit for a single resource, e.g. `limits.get(ResourceKind::MaxOpenFiles)`.
+    pub fn get(&self, kind: ResourceKind) -> &Limit {
+        match kind {
+            ResourceKind::MaxCpuTime => &self.max_cpu_time,
+            ResourceKind::MaxFileSize => &self.max_file_size,
+            ResourceKind::MaxDataSize => &self.max_data_size,
+            ResourceKind::MaxStackSize => &self.max_stack_size,
+            ResourceKind::MaxCoreFileSize => &self.max_core_file_size,
+            ResourceKind::MaxResidentSet => &self.max_resident_set,
+            ResourceKind::MaxProcesses => &self.max_processes,
+            ResourceKind::MaxOpenFiles => &self.max_open_files,
+            ResourceKind::MaxLockedMemory => &self.max_locked_memory,
+            ResourceKind::MaxAddressSpace => &self.max_address_space,
+            ResourceKind::MaxFileLocks => &self.max_file_locks,
+            ResourceKind::MaxPendingSignals => &self.max_pending_signals,
+            ResourceKind::MaxMsgqueueSize => &self.max_msgqueue_size,
+            ResourceKind::MaxNicePriority => &self.max_nice_priority,
+            ResourceKind::MaxRealtimePriority => &self.max_realtime_priority,
+            ResourceKind::MaxRealtimeTimeout => &self.max_realtime_timeout,
+        }
+    }
+
+    /// Iterate over every limit in this structure, paired with the `ResourceKind` that identifies
+    /// it, in the stable order of [`ResourceKind::ALL`]. This enables generic, `ulimit -a`-style
+    /// tabular reporting without referencing each of the sixteen fields by name.
+    pub fn iter(&self) -> impl Iterator<Item = (ResourceKind, &Limit)> {
+        ResourceKind::ALL
+            .iter()
+            .map(move |&kind| (kind, self.get(kind)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Limit, Limits, ResourceKind, Unit};
+
+    #[test]
+    fn test_iter_yields_all_resources_in_order() {
+        let limits = Limits::default();
+        let kinds: Vec<ResourceKind> = limits.iter().map(|(kind, _)| kind).collect();
+
+        assert_eq!(kinds, ResourceKind::ALL);
+    }
+
+    #[test]
+    fn test_get_matches_the_named_field() {
+        let mut limits = Limits::default();
+        limits.max_open_files = Limit {
+            soft: Some(1024),
+            hard: Some(4096),
+            unit: Unit::Files,
+        };
+
+        assert_eq!(
+            limits.get(ResourceKind::MaxOpenFiles),
+            &limits.max_open_files
+        );
+    }
+
+    #[test]
+    fn test_resource_kind_flag_and_unit() {
+        assert_eq!(ResourceKind::MaxOpenFiles.flag(), "-n");
+        assert_eq!(ResourceKind::MaxOpenFiles.label(), "Max open files");
+        assert_eq!(ResourceKind::MaxOpenFiles.unit(), Unit::Files);
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use crate::{Limit, Limits, Unit};
+
+    #[test]
+    fn test_limit_roundtrip() {
+        let limit = Limit {
+            soft: Some(1024),
+            hard: None,
+            unit: Unit::Bytes,
+        };
+
+        let json = serde_json::to_string(&limit).unwrap();
+        assert_eq!(json, r#"{"soft":1024,"hard":null,"unit":"Bytes"}"#);
+
+        let deserialized: Limit = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, limit);
+    }
+
+    #[test]
+    fn test_limits_roundtrip() {
+        let limits = Limits::default();
+
+        let json = serde_json::to_string(&limits).unwrap();
+        let deserialized: Limits = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(deserialized, limits);
+    }
+}
@@ -1,40 +1,124 @@
-/// A limit for a GNU/Linux specific limitable property.
+use crate::{Limit, Limits, ResourceKind, Unit};
+
+impl Unit {
+    /// Parse the `Units` column of `/proc/<pid>/limits` into a `Unit`. Unrecognized or empty
+    /// strings (e.g. `Max nice priority`, which carries no unit) default to `Unitless`.
+    fn from_str(value: &str) -> Self {
+        match value {
+            "seconds" => Unit::Seconds,
+            "bytes" => Unit::Bytes,
+            "us" => Unit::Microseconds,
+            "processes" => Unit::Processes,
+            "files" => Unit::Files,
+            "signals" => Unit::Signals,
+            "locks" => Unit::Locks,
+            _ => Unit::Unitless,
+        }
+    }
+}
+
+impl ResourceKind {
+    /// Map a `ResourceKind` to the `RLIMIT_*` constant that the kernel uses to identify it.
+    fn as_rlimit_resource(self) -> libc::c_uint {
+        match self {
+            ResourceKind::MaxCpuTime => libc::RLIMIT_CPU,
+            ResourceKind::MaxFileSize => libc::RLIMIT_FSIZE,
+            ResourceKind::MaxDataSize => libc::RLIMIT_DATA,
+            ResourceKind::MaxStackSize => libc::RLIMIT_STACK,
+            ResourceKind::MaxCoreFileSize => libc::RLIMIT_CORE,
+            ResourceKind::MaxResidentSet => libc::RLIMIT_RSS,
+            ResourceKind::MaxProcesses => libc::RLIMIT_NPROC,
+            ResourceKind::MaxOpenFiles => libc::RLIMIT_NOFILE,
+            ResourceKind::MaxLockedMemory => libc::RLIMIT_MEMLOCK,
+            ResourceKind::MaxAddressSpace => libc::RLIMIT_AS,
+            ResourceKind::MaxFileLocks => libc::RLIMIT_LOCKS,
+            ResourceKind::MaxPendingSignals => libc::RLIMIT_SIGPENDING,
+            ResourceKind::MaxMsgqueueSize => libc::RLIMIT_MSGQUEUE,
+            ResourceKind::MaxNicePriority => libc::RLIMIT_NICE,
+            ResourceKind::MaxRealtimePriority => libc::RLIMIT_RTPRIO,
+            ResourceKind::MaxRealtimeTimeout => libc::RLIMIT_RTTIME,
+        }
+    }
+}
+
+/// Convert a `Limit` into the `libc::rlimit64` that `prlimit64(2)` expects, mapping `None` to
+/// `RLIM_INFINITY`.
+fn limit_to_rlimit64(limit: &Limit) -> libc::rlimit64 {
+    libc::rlimit64 {
+        rlim_cur: limit.soft.unwrap_or(libc::RLIM_INFINITY),
+        rlim_max: limit.hard.unwrap_or(libc::RLIM_INFINITY),
+    }
+}
+
+/// Convert a `libc::rlimit64`, as returned by `prlimit64(2)`, back into a `Limit`, mapping
+/// `RLIM_INFINITY` back to `None`.
+fn rlimit64_to_limit(rlimit: libc::rlimit64, unit: Unit) -> Limit {
+    let to_option = |value: u64| {
+        if value == libc::RLIM_INFINITY {
+            None
+        } else {
+            Some(value)
+        }
+    };
+
+    Limit {
+        soft: to_option(rlimit.rlim_cur),
+        hard: to_option(rlimit.rlim_max),
+        unit,
+    }
+}
+
+/// Set a limit for an arbitrary process, identified by its process identifier.
 ///
-/// Any given limit always contain a _soft_ and a _hard_ limit.
+/// This calls `prlimit(2)` under the hood, which atomically sets the new limit and returns the
+/// previous one, so that callers can restore it later if needed.
 ///
-/// A soft or hard limited whose value is `None` here means there is no actual limit, i.e. the value
-/// found in `/proc/<pid>/limits` is `unlimited`.
-#[derive(Debug, Default, Eq, PartialEq)]
-pub struct Limit {
-    pub soft: Option<u32>,
-    pub hard: Option<u32>,
+/// Passing `0` as `pid` sets the limit for the calling process, same as `set_own_limit`.
+pub fn set_pid_limit(
+    pid: u32,
+    resource: ResourceKind,
+    limit: Limit,
+) -> Result<Limit, crate::Error> {
+    let new_rlimit = limit_to_rlimit64(&limit);
+    let mut old_rlimit = std::mem::MaybeUninit::<libc::rlimit64>::uninit();
+
+    let result = unsafe {
+        libc::prlimit64(
+            pid as libc::pid_t,
+            resource.as_rlimit_resource(),
+            &new_rlimit,
+            old_rlimit.as_mut_ptr(),
+        )
+    };
+
+    if result != 0 {
+        return Err(crate::Error::SetLimitFailed(
+            pid,
+            std::io::Error::last_os_error(),
+        ));
+    }
+
+    // Safe because `prlimit64` only returned successfully after writing the previous limit into
+    // `old_rlimit`.
+    Ok(rlimit64_to_limit(
+        unsafe { old_rlimit.assume_init() },
+        resource.unit(),
+    ))
 }
 
-/// A structure containing all possible properties that can be limited by a GNU/Linux operating
-/// system.
-#[derive(Debug, Default, Eq, PartialEq)]
-pub struct Limits {
-    pub max_cpu_time: Limit,
-    pub max_file_size: Limit,
-    pub max_data_size: Limit,
-    pub max_stack_size: Limit,
-    pub max_core_file_size: Limit,
-    pub max_resident_set: Limit,
-    pub max_processes: Limit,
-    pub max_open_files: Limit,
-    pub max_locked_memory: Limit,
-    pub max_address_space: Limit,
-    pub max_file_locks: Limit,
-    pub max_pending_signals: Limit,
-    pub max_msgqueue_size: Limit,
-    pub max_nice_priority: Limit,
-    pub max_realtime_priority: Limit,
-    pub max_realtime_timeout: Limit,
+/// Set a limit for the process in which we are running (our own process id).
+///
+/// Returns the previous limit, so that callers can restore it later if needed.
+pub fn set_own_limit(resource: ResourceKind, limit: Limit) -> Result<Limit, crate::Error> {
+    set_pid_limit(0, resource, limit)
 }
 
 impl Limits {
     /// Set properties on a `Limit` structure, as read from strings.
     ///
+    /// `unit_string` is the `Units` column of `/proc/<pid>/limits`; an empty string (as found for
+    /// `Max nice priority` and `Max realtime priority`) is treated as `Unit::Unitless`.
+    ///
     /// # Examples
     ///
     /// ```rust
@@ -44,16 +128,23 @@ impl Limits {
     /// let mut limits = Limits::default();
     ///
     /// // Trying to set a non-existing property should do nothing
-    /// limits.set_property_from_strings("Does_not_exist", "123", "456");
+    /// limits.set_property_from_strings("Does_not_exist", "123", "456", "bytes");
     /// assert_eq!(limits, Limits::default());
     ///
     /// // Let's set a limit for a existing property and assert that the limit is actually stored in
     /// // the structure
-    /// limits.set_property_from_strings("Max file locks", "123", "456");
-    /// assert_eq!(limits.max_file_locks, Limit { soft: Some(123), hard: Some(456) })
+    /// limits.set_property_from_strings("Max file locks", "123", "456", "locks");
+    /// assert_eq!(limits.max_file_locks.soft, Some(123));
+    /// assert_eq!(limits.max_file_locks.hard, Some(456));
     ///
     /// ```
-    pub fn set_property_from_strings(&mut self, name: &str, soft_string: &str, hard_string: &str) {
+    pub fn set_property_from_strings(
+        &mut self,
+        name: &str,
+        soft_string: &str,
+        hard_string: &str,
+        unit_string: &str,
+    ) {
         use std::str::FromStr;
 
         let lower_case = name.to_lowercase();
@@ -61,20 +152,22 @@ impl Limits {
         let soft = if soft_string == "unlimited" {
             None
         } else {
-            u32::from_str(soft_string).ok()
+            u64::from_str(soft_string).ok()
         };
 
         let hard = if hard_string == "unlimited" {
             None
         } else {
-            u32::from_str(hard_string).ok()
+            u64::from_str(hard_string).ok()
         };
 
-        let new_limit = Limit { soft, hard };
+        let unit = Unit::from_str(unit_string);
+
+        let new_limit = Limit { soft, hard, unit };
 
         match lower_case.as_str() {
             "max cpu time" => self.max_cpu_time = new_limit,
-            "max file_size" => self.max_file_size = new_limit,
+            "max file size" => self.max_file_size = new_limit,
             "max data size" => self.max_data_size = new_limit,
             "max stack size" => self.max_stack_size = new_limit,
             "max core file size" => self.max_core_file_size = new_limit,
@@ -135,7 +228,8 @@ where
         let (property, values) = line.split_at(26);
         let property = property.trim();
         let values: Vec<&str> = values.split_whitespace().collect();
-        limits.set_property_from_strings(property, values[0], values[1]);
+        let unit = values.get(2).copied().unwrap_or("");
+        limits.set_property_from_strings(property, values[0], values[1], unit);
     }
 
     Ok(limits)
@@ -143,7 +237,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::{Limit, Limits};
+    use crate::{Limit, Limits, ResourceKind, Unit};
 
     #[test]
     fn test_own_limits_does_not_panic() {
@@ -155,10 +249,35 @@ mod tests {
         crate::get_pid_limits(1).unwrap();
     }
 
+    #[test]
+    fn test_set_own_limit_roundtrip() {
+        let limits = crate::get_own_limits().unwrap();
+        let previous = limits.max_open_files;
+
+        // Re-set the very same limit, which is always permitted, and check that the previous
+        // value reported back by `prlimit64` matches what we just read.
+        let old_limit = super::set_own_limit(ResourceKind::MaxOpenFiles, previous).unwrap();
+        assert_eq!(old_limit, previous);
+    }
+
+    #[test]
+    fn test_set_pid_limit_invalid_pid_fails() {
+        let error =
+            super::set_pid_limit(std::u32::MAX, ResourceKind::MaxOpenFiles, Limit::default())
+                .unwrap_err();
+
+        assert!(matches!(
+            error,
+            crate::Error::SetLimitFailed(std::u32::MAX, _)
+        ));
+    }
+
     #[test]
     fn test_proc_file_not_found() {
         let error = format!("{:?}", super::get_pid_limits(std::u32::MAX).unwrap_err());
-        let expected_error = String::from(r#"ProcFileNotFound("/proc/4294967295/limits", Os { code: 2, kind: NotFound, message: "No such file or directory" })"#);
+        let expected_error = String::from(
+            r#"ProcFileNotFound("/proc/4294967295/limits", Os { code: 2, kind: NotFound, message: "No such file or directory" })"#,
+        );
 
         assert_eq!(error, expected_error);
     }
@@ -173,6 +292,51 @@ mod tests {
         assert_eq!(limits, expected_limits);
     }
 
+    #[test]
+    fn test_limit_as_bytes_and_duration() {
+        let bytes_limit = Limit {
+            soft: Some(8388608),
+            hard: None,
+            unit: Unit::Bytes,
+        };
+        assert_eq!(bytes_limit.soft_as_bytes(), Some(8388608));
+        assert_eq!(bytes_limit.soft_as_duration(), None);
+
+        let seconds_limit = Limit {
+            soft: Some(60),
+            hard: Some(120),
+            unit: Unit::Seconds,
+        };
+        assert_eq!(
+            seconds_limit.soft_as_duration(),
+            Some(std::time::Duration::from_secs(60))
+        );
+        assert_eq!(
+            seconds_limit.hard_as_duration(),
+            Some(std::time::Duration::from_secs(120))
+        );
+        assert_eq!(seconds_limit.soft_as_bytes(), None);
+    }
+
+    #[test]
+    fn test_from_string_above_u32_max() {
+        let mut limits = Limits::default();
+
+        // `8589934592` is 8 GiB, i.e. twice `u32::MAX + 1`. A byte-denominated limit like this is
+        // common for `max_address_space`/`max_file_size`/`max_data_size` and must not be silently
+        // dropped to `None`.
+        limits.set_property_from_strings("Max address space", "8589934592", "unlimited", "bytes");
+
+        assert_eq!(
+            limits.max_address_space,
+            Limit {
+                soft: Some(8589934592),
+                hard: None,
+                unit: Unit::Bytes,
+            }
+        );
+    }
+
     #[test]
     fn test_from_correct_string() {
         let reader = std::io::Cursor::new(
@@ -197,46 +361,86 @@ Max realtime timeout      unlimited            unlimited            us"#,
         let limits = super::get_limits_from_reader(reader).unwrap();
 
         let expected_limits = Limits {
-            max_cpu_time: Default::default(),
-            max_file_size: Default::default(),
-            max_data_size: Default::default(),
+            max_cpu_time: Limit {
+                soft: None,
+                hard: None,
+                unit: Unit::Seconds,
+            },
+            max_file_size: Limit {
+                soft: None,
+                hard: None,
+                unit: Unit::Bytes,
+            },
+            max_data_size: Limit {
+                soft: None,
+                hard: None,
+                unit: Unit::Bytes,
+            },
             max_stack_size: Limit {
                 soft: Some(8388608),
                 hard: None,
+                unit: Unit::Bytes,
+            },
+            max_core_file_size: Limit {
+                soft: None,
+                hard: None,
+                unit: Unit::Bytes,
+            },
+            max_resident_set: Limit {
+                soft: None,
+                hard: None,
+                unit: Unit::Bytes,
             },
-            max_core_file_size: Default::default(),
-            max_resident_set: Default::default(),
             max_processes: Limit {
                 soft: Some(62935),
                 hard: Some(62935),
+                unit: Unit::Processes,
             },
             max_open_files: Limit {
                 soft: Some(1024),
                 hard: Some(524288),
+                unit: Unit::Files,
             },
             max_locked_memory: Limit {
                 soft: Some(65536),
                 hard: Some(65536),
+                unit: Unit::Bytes,
+            },
+            max_address_space: Limit {
+                soft: None,
+                hard: None,
+                unit: Unit::Bytes,
+            },
+            max_file_locks: Limit {
+                soft: None,
+                hard: None,
+                unit: Unit::Locks,
             },
-            max_address_space: Default::default(),
-            max_file_locks: Default::default(),
             max_pending_signals: Limit {
                 soft: Some(62935),
                 hard: Some(62935),
+                unit: Unit::Signals,
             },
             max_msgqueue_size: Limit {
                 soft: Some(819200),
                 hard: Some(819200),
+                unit: Unit::Bytes,
             },
             max_nice_priority: Limit {
                 soft: Some(0),
                 hard: Some(0),
+                unit: Unit::Unitless,
             },
             max_realtime_priority: Limit {
                 soft: Some(99),
                 hard: Some(99),
+                unit: Unit::Unitless,
+            },
+            max_realtime_timeout: Limit {
+                soft: None,
+                hard: None,
+                unit: Unit::Microseconds,
             },
-            max_realtime_timeout: Default::default(),
         };
 
         assert_eq!(limits, expected_limits);